@@ -0,0 +1,30 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use glsl_to_spirv::ShaderType;
+
+fn main() {
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+
+    compile_shader("shader/text.vert", ShaderType::Vertex, out_dir.join("text.vert.spv"));
+    compile_shader("shader/text.frag", ShaderType::Fragment, out_dir.join("text.frag.spv"));
+    compile_shader("shader/clip.frag", ShaderType::Fragment, out_dir.join("clip.frag.spv"));
+
+    println!("cargo:rerun-if-changed=shader/text.vert");
+    println!("cargo:rerun-if-changed=shader/text.frag");
+    println!("cargo:rerun-if-changed=shader/clip.frag");
+}
+
+fn compile_shader(source_path: &str, shader_type: ShaderType, out_path: PathBuf) {
+    let source = fs::read_to_string(source_path)
+        .unwrap_or_else(|e| panic!("failed to read {}: {}", source_path, e));
+
+    let mut compiled = glsl_to_spirv::compile(&source, shader_type)
+        .unwrap_or_else(|e| panic!("failed to compile {}: {}", source_path, e));
+
+    let mut spirv = Vec::new();
+    std::io::copy(&mut compiled, &mut spirv).unwrap();
+
+    fs::write(out_path, spirv).unwrap();
+}
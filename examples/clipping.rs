@@ -0,0 +1,138 @@
+use wgpu_glyph::{GlyphBrushBuilder, Scale, Section};
+
+fn main() -> Result<(), String> {
+    env_logger::init();
+
+    // Initialize GPU
+    let adapter = wgpu::Adapter::request(&wgpu::RequestAdapterOptions {
+        power_preference: wgpu::PowerPreference::HighPerformance,
+        backends: wgpu::BackendBit::PRIMARY,
+    })
+    .expect("Request adapter");
+
+    let (mut device, mut queue) = adapter.request_device(&wgpu::DeviceDescriptor {
+        extensions: wgpu::Extensions {
+            anisotropic_filtering: false,
+        },
+        limits: wgpu::Limits { max_bind_groups: 1 },
+    });
+
+    // Open window and create a surface
+    let event_loop = winit::event_loop::EventLoop::new();
+
+    let window = winit::window::WindowBuilder::new()
+        .with_resizable(false)
+        .build(&event_loop)
+        .unwrap();
+
+    let surface = wgpu::Surface::create(&window);
+
+    let render_format = wgpu::TextureFormat::Bgra8UnormSrgb;
+    let size = window.inner_size();
+
+    let mut swap_chain = device.create_swap_chain(
+        &surface,
+        &wgpu::SwapChainDescriptor {
+            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+            format: render_format,
+            width: size.width,
+            height: size.height,
+            present_mode: wgpu::PresentMode::Vsync,
+        },
+    );
+
+    // Clipping needs a stencil aspect to write the clip mask into.
+    let inconsolata: &[u8] = include_bytes!("Inconsolata-Regular.ttf");
+    let mut glyph_brush = GlyphBrushBuilder::using_font_bytes(inconsolata)
+        .depth_stencil_state(wgpu::DepthStencilStateDescriptor {
+            format: wgpu::TextureFormat::Depth24PlusStencil8,
+            depth_write_enabled: false,
+            depth_compare: wgpu::CompareFunction::Always,
+            stencil_front: wgpu::StencilStateFaceDescriptor::IGNORE,
+            stencil_back: wgpu::StencilStateFaceDescriptor::IGNORE,
+            stencil_read_mask: 0,
+            stencil_write_mask: 0,
+        })
+        .build(&mut device, render_format);
+
+    let mut depth_view =
+        glyph_brush.create_depth_texture_view(&device, size.width, size.height);
+
+    event_loop.run(move |event, _, control_flow| match event {
+        winit::event::Event::WindowEvent {
+            event: winit::event::WindowEvent::CloseRequested,
+            ..
+        } => *control_flow = winit::event_loop::ControlFlow::Exit,
+        winit::event::Event::WindowEvent {
+            event: winit::event::WindowEvent::Resized(new_size),
+            ..
+        } => {
+            let size = new_size;
+
+            swap_chain = device.create_swap_chain(
+                &surface,
+                &wgpu::SwapChainDescriptor {
+                    usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+                    format: render_format,
+                    width: size.width,
+                    height: size.height,
+                    present_mode: wgpu::PresentMode::Vsync,
+                },
+            );
+
+            depth_view = glyph_brush.create_depth_texture_view(&device, size.width, size.height);
+        }
+        winit::event::Event::MainEventsCleared => {
+            let mut encoder =
+                device.create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 });
+
+            let frame = swap_chain.get_next_texture();
+
+            // A panel in the top-left quarter of the window: the section's
+            // own text overflows it (`lipsum.txt` is far taller than the
+            // panel), but `queue_clipped` discards whatever extends past
+            // `clip_bounds` at the pixel level instead of only culling
+            // whole glyphs outside the section's layout bounds.
+            let panel_bounds = [
+                20.0,
+                20.0,
+                size.width as f32 / 2.0,
+                size.height as f32 / 2.0,
+            ];
+
+            glyph_brush.queue_clipped(
+                Section {
+                    screen_position: (30.0, 30.0),
+                    bounds: (size.width as f32, size.height as f32),
+                    text: &include_str!("lipsum.txt").replace("\n\n", ""),
+                    scale: Scale::uniform(24.0),
+                    color: [0.05, 0.05, 0.1, 1.0],
+                    ..Section::default()
+                },
+                panel_bounds,
+            );
+
+            glyph_brush
+                .draw_queued(
+                    &mut device,
+                    &mut encoder,
+                    &frame.view,
+                    wgpu::RenderPassDepthStencilAttachmentDescriptor {
+                        attachment: &depth_view,
+                        depth_load_op: wgpu::LoadOp::Clear,
+                        depth_store_op: wgpu::StoreOp::Store,
+                        stencil_load_op: wgpu::LoadOp::Clear,
+                        stencil_store_op: wgpu::StoreOp::Store,
+                        clear_depth: 1.0,
+                        clear_stencil: 0,
+                    },
+                    size.width,
+                    size.height,
+                )
+                .expect("Draw queued");
+
+            queue.submit(&[encoder.finish()]);
+        }
+        _ => {}
+    })
+}
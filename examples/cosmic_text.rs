@@ -0,0 +1,136 @@
+use cosmic_text::{Attrs, FontSystem, Metrics};
+use wgpu_glyph::cosmic::CosmicGlyphBrush;
+
+fn main() -> Result<(), String> {
+    env_logger::init();
+
+    // Initialize GPU
+    let adapter = wgpu::Adapter::request(&wgpu::RequestAdapterOptions {
+        power_preference: wgpu::PowerPreference::HighPerformance,
+        backends: wgpu::BackendBit::PRIMARY,
+    })
+    .expect("Request adapter");
+
+    let (mut device, mut queue) = adapter.request_device(&wgpu::DeviceDescriptor {
+        extensions: wgpu::Extensions {
+            anisotropic_filtering: false,
+        },
+        limits: wgpu::Limits { max_bind_groups: 1 },
+    });
+
+    // Open window and create a surface
+    let event_loop = winit::event_loop::EventLoop::new();
+
+    let window = winit::window::WindowBuilder::new()
+        .with_resizable(false)
+        .build(&event_loop)
+        .unwrap();
+
+    let surface = wgpu::Surface::create(&window);
+
+    let render_format = wgpu::TextureFormat::Bgra8UnormSrgb;
+    let size = window.inner_size();
+
+    let swap_chain = device.create_swap_chain(
+        &surface,
+        &wgpu::SwapChainDescriptor {
+            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+            format: render_format,
+            width: size.width,
+            height: size.height,
+            present_mode: wgpu::PresentMode::Vsync,
+        },
+    );
+
+    // `FontSystem` owns the fallback font set `cosmic-text` shapes against
+    // (system fonts plus anything loaded with `db_mut().load_font_data`),
+    // so Arabic, Hebrew or CJK runs in the same section automatically pick
+    // up a font that can render them.
+    let font_system = FontSystem::new();
+
+    let mut glyph_brush = CosmicGlyphBrush::new(
+        &mut device,
+        render_format,
+        wgpu::DepthStencilStateDescriptor {
+            format: wgpu::TextureFormat::Depth32Float,
+            depth_write_enabled: false,
+            depth_compare: wgpu::CompareFunction::Always,
+            stencil_front: wgpu::StencilStateFaceDescriptor::IGNORE,
+            stencil_back: wgpu::StencilStateFaceDescriptor::IGNORE,
+            stencil_read_mask: 0,
+            stencil_write_mask: 0,
+        },
+        font_system,
+    );
+
+    let mut swap_chain = swap_chain;
+
+    let depth_texture = device.create_texture(&wgpu::TextureDescriptor {
+        size: wgpu::Extent3d {
+            width: size.width,
+            height: size.height,
+            depth: 1,
+        },
+        array_layer_count: 1,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Depth32Float,
+        usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+    });
+    let depth_view = depth_texture.create_default_view();
+
+    event_loop.run(move |event, _, control_flow| {
+        if let winit::event::Event::WindowEvent {
+            event: winit::event::WindowEvent::CloseRequested,
+            ..
+        } = event
+        {
+            *control_flow = winit::event_loop::ControlFlow::Exit;
+            return;
+        }
+
+        if event == winit::event::Event::MainEventsCleared {
+            let mut encoder =
+                device.create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 });
+
+            let frame = swap_chain.get_next_texture();
+
+            // Mixed-direction text: Arabic shapes right-to-left and reorders
+            // with the surrounding Latin text via Unicode bidi, which the
+            // builtin `glyph_brush` layout can't do on its own.
+            glyph_brush.queue(
+                &mut device,
+                &mut encoder,
+                "Hello, مرحبا, world!",
+                Attrs::new(),
+                Metrics::new(28.0, 34.0),
+                (size.width as f32, size.height as f32),
+                (30.0, 30.0),
+                [0.1, 0.1, 0.1, 1.0],
+                0.0,
+            );
+
+            glyph_brush
+                .draw_queued(
+                    &mut device,
+                    &mut encoder,
+                    &frame.view,
+                    wgpu::RenderPassDepthStencilAttachmentDescriptor {
+                        attachment: &depth_view,
+                        depth_load_op: wgpu::LoadOp::Clear,
+                        depth_store_op: wgpu::StoreOp::Store,
+                        stencil_load_op: wgpu::LoadOp::Clear,
+                        stencil_store_op: wgpu::StoreOp::Store,
+                        clear_depth: 1.0,
+                        clear_stencil: 0,
+                    },
+                    size.width,
+                    size.height,
+                )
+                .expect("Draw queued");
+
+            queue.submit(&[encoder.finish()]);
+        }
+    })
+}
@@ -1,17 +1,16 @@
-use raw_window_handle::HasRawWindowHandle;
 use wgpu_glyph::{GlyphBrushBuilder, Scale, Section};
 
 fn main() -> Result<(), String> {
     env_logger::init();
 
     // Initialize GPU
-    let instance = wgpu::Instance::new();
-
-    let adapter = instance.request_adapter(&wgpu::RequestAdapterOptions {
+    let adapter = wgpu::Adapter::request(&wgpu::RequestAdapterOptions {
         power_preference: wgpu::PowerPreference::HighPerformance,
-    });
+        backends: wgpu::BackendBit::PRIMARY,
+    })
+    .expect("Request adapter");
 
-    let mut device = adapter.request_device(&wgpu::DeviceDescriptor {
+    let (mut device, mut queue) = adapter.request_device(&wgpu::DeviceDescriptor {
         extensions: wgpu::Extensions {
             anisotropic_filtering: false,
         },
@@ -26,25 +25,23 @@ fn main() -> Result<(), String> {
         .build(&event_loop)
         .unwrap();
 
-    let surface = instance.create_surface(window.raw_window_handle());
+    let surface = wgpu::Surface::create(&window);
 
     // Prepare swap chain and depth buffer
     let render_format = wgpu::TextureFormat::Bgra8UnormSrgb;
-    let mut size = window.inner_size().to_physical(window.hidpi_factor());
+    let mut size = window.inner_size();
 
     let mut swap_chain = device.create_swap_chain(
         &surface,
         &wgpu::SwapChainDescriptor {
             usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
             format: render_format,
-            width: size.width.round() as u32,
-            height: size.height.round() as u32,
+            width: size.width,
+            height: size.height,
             present_mode: wgpu::PresentMode::Vsync,
         },
     );
 
-    let mut depth_view = create_depth_view(&device, size);
-
     // Prepare glyph_brush
     let inconsolata: &[u8] = include_bytes!("Inconsolata-Regular.ttf");
     let mut glyph_brush = GlyphBrushBuilder::using_font_bytes(inconsolata)
@@ -59,6 +56,11 @@ fn main() -> Result<(), String> {
         })
         .build(&mut device, render_format);
 
+    // Allocate a depth buffer matching the format the brush was built with,
+    // so a scene using a different depth/stencil format (e.g. a shared
+    // `Depth24PlusStencil8` buffer) can't drift out of sync with it.
+    let mut depth_view = glyph_brush.create_depth_texture_view(&device, size.width, size.height);
+
     // Render loop
     event_loop.run(move |event, _, control_flow| {
         match event {
@@ -70,22 +72,22 @@ fn main() -> Result<(), String> {
                 event: winit::event::WindowEvent::Resized(new_size),
                 ..
             } => {
-                size = new_size.to_physical(window.hidpi_factor());
+                size = new_size;
 
                 swap_chain = device.create_swap_chain(
                     &surface,
                     &wgpu::SwapChainDescriptor {
                         usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
                         format: render_format,
-                        width: size.width.round() as u32,
-                        height: size.height.round() as u32,
+                        width: size.width,
+                        height: size.height,
                         present_mode: wgpu::PresentMode::Vsync,
                     },
                 );
 
-                depth_view = create_depth_view(&device, size);
+                depth_view = glyph_brush.create_depth_texture_view(&device, size.width, size.height);
             }
-            winit::event::Event::EventsCleared => {
+            winit::event::Event::MainEventsCleared => {
                 // Get a command encoder for the current frame
                 let mut encoder = device.create_command_encoder(
                     &wgpu::CommandEncoderDescriptor { todo: 0 },
@@ -157,35 +159,46 @@ fn main() -> Result<(), String> {
                             clear_depth: -1.0,
                             clear_stencil: 0,
                         },
-                        size.width.round() as u32,
-                        size.height.round() as u32,
+                        size.width,
+                        size.height,
+                    )
+                    .expect("Draw queued");
+
+                // Queue a label that should be hidden wherever the scene
+                // above already wrote closer depth, but must not disturb
+                // that depth buffer itself -- e.g. a later UI pass still
+                // needs it intact for its own occlusion test.
+                glyph_brush.queue(Section {
+                    screen_position: (30.0, size.height as f32 - 60.0),
+                    text: "Occluded by depth, drawn read-only",
+                    scale: Scale::uniform(40.0),
+                    color: [0.8, 0.2, 0.2, 1.0],
+                    z: 0.1,
+                    ..Section::default()
+                });
+
+                glyph_brush
+                    .draw_queued_with_depth_read_only(
+                        &mut device,
+                        &mut encoder,
+                        &frame.view,
+                        wgpu::RenderPassDepthStencilAttachmentDescriptor {
+                            attachment: &depth_view,
+                            depth_load_op: wgpu::LoadOp::Load,
+                            depth_store_op: wgpu::StoreOp::Store,
+                            stencil_load_op: wgpu::LoadOp::Load,
+                            stencil_store_op: wgpu::StoreOp::Store,
+                            clear_depth: -1.0,
+                            clear_stencil: 0,
+                        },
+                        size.width,
+                        size.height,
                     )
                     .expect("Draw queued");
 
-                device.get_queue().submit(&[encoder.finish()]);
+                queue.submit(&[encoder.finish()]);
             }
             _ => {}
         }
     })
 }
-
-fn create_depth_view(
-    device: &wgpu::Device,
-    size: winit::dpi::PhysicalSize,
-) -> wgpu::TextureView {
-    let depth_texture = device.create_texture(&wgpu::TextureDescriptor {
-        size: wgpu::Extent3d {
-            width: size.width as u32,
-            height: size.height as u32,
-            depth: 1,
-        },
-        array_layer_count: 1,
-        mip_level_count: 1,
-        sample_count: 1,
-        dimension: wgpu::TextureDimension::D2,
-        format: wgpu::TextureFormat::Depth32Float,
-        usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
-    });
-
-    depth_texture.create_default_view()
-}
@@ -0,0 +1,126 @@
+use wgpu_glyph::{GlyphBrushBuilder, Scale, Section, Viewport};
+
+fn main() -> Result<(), String> {
+    env_logger::init();
+
+    // Initialize GPU
+    let adapter = wgpu::Adapter::request(&wgpu::RequestAdapterOptions {
+        power_preference: wgpu::PowerPreference::HighPerformance,
+        backends: wgpu::BackendBit::PRIMARY,
+    })
+    .expect("Request adapter");
+
+    let (mut device, mut queue) = adapter.request_device(&wgpu::DeviceDescriptor {
+        extensions: wgpu::Extensions {
+            anisotropic_filtering: false,
+        },
+        limits: wgpu::Limits { max_bind_groups: 1 },
+    });
+
+    // Open window and create a surface
+    let event_loop = winit::event_loop::EventLoop::new();
+
+    let window = winit::window::WindowBuilder::new()
+        .with_resizable(false)
+        .build(&event_loop)
+        .unwrap();
+
+    let surface = wgpu::Surface::create(&window);
+
+    let render_format = wgpu::TextureFormat::Bgra8UnormSrgb;
+    let mut size = window.inner_size();
+
+    let mut swap_chain = device.create_swap_chain(
+        &surface,
+        &wgpu::SwapChainDescriptor {
+            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+            format: render_format,
+            width: size.width,
+            height: size.height,
+            present_mode: wgpu::PresentMode::Vsync,
+        },
+    );
+
+    let inconsolata: &[u8] = include_bytes!("Inconsolata-Regular.ttf");
+    let mut glyph_brush =
+        GlyphBrushBuilder::using_font_bytes(inconsolata).build(&mut device, render_format);
+
+    event_loop.run(move |event, _, control_flow| match event {
+        winit::event::Event::WindowEvent {
+            event: winit::event::WindowEvent::CloseRequested,
+            ..
+        } => *control_flow = winit::event_loop::ControlFlow::Exit,
+        winit::event::Event::WindowEvent {
+            event: winit::event::WindowEvent::Resized(new_size),
+            ..
+        } => {
+            size = new_size;
+
+            swap_chain = device.create_swap_chain(
+                &surface,
+                &wgpu::SwapChainDescriptor {
+                    usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+                    format: render_format,
+                    width: size.width,
+                    height: size.height,
+                    present_mode: wgpu::PresentMode::Vsync,
+                },
+            );
+        }
+        winit::event::Event::MainEventsCleared => {
+            let mut encoder =
+                device.create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 });
+
+            let frame = swap_chain.get_next_texture();
+
+            {
+                let _ = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                        attachment: &frame.view,
+                        resolve_target: None,
+                        load_op: wgpu::LoadOp::Clear,
+                        store_op: wgpu::StoreOp::Store,
+                        clear_color: wgpu::Color {
+                            r: 0.4,
+                            g: 0.4,
+                            b: 0.4,
+                            a: 1.0,
+                        },
+                    }],
+                    depth_stencil_attachment: None,
+                });
+            }
+
+            // Render the same section into each quarter of the window
+            // through a `Viewport` sized and positioned to that quarter,
+            // rather than the full-surface transform `draw_queued` assumes.
+            let half_width = size.width / 2;
+            let half_height = size.height / 2;
+
+            for &origin in &[
+                [0, 0],
+                [half_width, 0],
+                [0, half_height],
+                [half_width, half_height],
+            ] {
+                glyph_brush.queue(Section {
+                    screen_position: (20.0, 20.0),
+                    text: "Tiled into a Viewport",
+                    scale: Scale::uniform(28.0),
+                    color: [0.05, 0.05, 0.1, 1.0],
+                    ..Section::default()
+                });
+
+                let mut viewport = Viewport::full_screen(half_width, half_height);
+                viewport.origin = origin;
+
+                glyph_brush
+                    .draw_queued_to(&mut device, &mut encoder, &frame.view, viewport)
+                    .expect("Draw queued");
+            }
+
+            queue.submit(&[encoder.finish()]);
+        }
+        _ => {}
+    })
+}
@@ -0,0 +1,210 @@
+//! An alternate [`GlyphBrush`]-like brush that shapes text with
+//! [`cosmic-text`] instead of `glyph_brush`'s built-in layout.
+//!
+//! Where [`GlyphBrush`] only handles left-to-right, single-font runs,
+//! [`CosmicGlyphBrush`] hands the section string and a font selection off
+//! to a `cosmic_text::Buffer`, which performs Unicode bidi reordering,
+//! grapheme clustering, ligatures and automatic fallback across the fonts
+//! registered with its `FontSystem`. The resulting positioned glyphs are
+//! rasterized with `swash` into the same GPU cache texture approach
+//! `draw_queued` already consumes, so this unblocks Arabic/Hebrew/CJK and
+//! mixed-font UI text that the builtin layout cannot render correctly.
+//!
+//! [`GlyphBrush`]: crate::GlyphBrush
+//! [`cosmic-text`]: https://github.com/pop-os/cosmic-text
+
+use std::collections::HashMap;
+
+use cosmic_text::{Attrs, Buffer, CacheKey, FontSystem, Metrics, SwashCache, SwashContent};
+
+use crate::pipeline::{CacheRect, DepthMode, Instance, Pipeline};
+
+/// Where in the GPU cache texture a rasterized `(font_id, glyph_id,
+/// subpixel_offset)` key's bitmap lives.
+#[derive(Debug, Clone, Copy)]
+struct CachedGlyph {
+    rect: CacheRect,
+    left: i32,
+    top: i32,
+}
+
+/// Shapes sections with `cosmic-text` and rasterizes their glyphs with
+/// `swash`, reusing [`Pipeline`] for GPU upload and drawing.
+pub struct CosmicGlyphBrush {
+    font_system: FontSystem,
+    swash_cache: SwashCache,
+    pipeline: Pipeline,
+    /// `None` for keys that rasterize to nothing worth drawing (whitespace,
+    /// zero-width joiners, or color glyphs this cache can't hold -- see
+    /// [`CosmicGlyphBrush::rasterize`]), cached so they aren't retried.
+    glyphs: HashMap<CacheKey, Option<CachedGlyph>>,
+    queued: Vec<Instance>,
+}
+
+impl CosmicGlyphBrush {
+    /// Creates a brush from an existing `cosmic_text::FontSystem` (which
+    /// owns the fallback font set), building its render pipeline against
+    /// `render_format` and `depth_stencil_state`.
+    pub fn new(
+        device: &mut wgpu::Device,
+        render_format: wgpu::TextureFormat,
+        depth_stencil_state: wgpu::DepthStencilStateDescriptor,
+        font_system: FontSystem,
+    ) -> CosmicGlyphBrush {
+        let pipeline = Pipeline::new(device, render_format, depth_stencil_state, 1024, 1024);
+
+        CosmicGlyphBrush {
+            font_system,
+            swash_cache: SwashCache::new(),
+            pipeline,
+            glyphs: HashMap::new(),
+            queued: Vec::new(),
+        }
+    }
+
+    /// Shapes `text` with `attrs` (font family, weight, style, ...) wrapped
+    /// to `bounds` pixels, and queues its glyphs for the next
+    /// [`draw_queued`]. Can be called multiple times before drawing to
+    /// batch several runs, mixed fonts and scripts together.
+    ///
+    /// [`draw_queued`]: CosmicGlyphBrush::draw_queued
+    #[allow(clippy::too_many_arguments)]
+    pub fn queue(
+        &mut self,
+        device: &mut wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        text: &str,
+        attrs: Attrs,
+        metrics: Metrics,
+        bounds: (f32, f32),
+        screen_position: (f32, f32),
+        color: [f32; 4],
+        z: f32,
+    ) {
+        let mut buffer = Buffer::new(&mut self.font_system, metrics);
+        buffer.set_size(&mut self.font_system, bounds.0, bounds.1);
+        buffer.set_text(&mut self.font_system, text, attrs);
+        buffer.shape_until_scroll(&mut self.font_system);
+
+        for run in buffer.layout_runs() {
+            for glyph in run.glyphs {
+                // `cosmic-text` 0.8 has no `LayoutGlyph::physical` helper: its
+                // `x_int`/`y_int` are already the pixel-quantized position of
+                // the glyph within the buffer's own layout, so the caller's
+                // `screen_position` is added on top as a plain integer
+                // translation, same as `Buffer::draw`'s built-in renderer does.
+                let cache_key = glyph.cache_key;
+
+                let cached = match self.glyphs.get(&cache_key) {
+                    Some(cached) => *cached,
+                    None => {
+                        let cached = self.rasterize(device, encoder, cache_key);
+                        self.glyphs.insert(cache_key, cached);
+                        cached
+                    }
+                };
+
+                let cached = match cached {
+                    Some(cached) => cached,
+                    None => continue,
+                };
+
+                let x = (screen_position.0 as i32 + glyph.x_int + cached.left) as f32;
+                // `cached.top` (swash's `placement.top`) is measured upward
+                // from the baseline, so the bitmap's top edge in y-down
+                // screen space is the baseline position *minus* it, not
+                // plus -- otherwise glyphs end up mirrored across the
+                // baseline.
+                let y = (screen_position.1 as i32 + run.line_y as i32 + glyph.y_int - cached.top)
+                    as f32;
+
+                self.queued.push(Instance::from_cache_rect(
+                    cached.rect,
+                    self.pipeline.cache_dimensions(),
+                    x,
+                    y,
+                    z,
+                    color,
+                ));
+            }
+        }
+    }
+
+    /// Rasterizes a `(font_id, glyph_id, subpixel)` key that hasn't been
+    /// seen yet and uploads it into the shared GPU cache texture, so
+    /// repeated glyphs across runs and frames aren't re-rasterized.
+    ///
+    /// Returns `None` for glyphs with nothing to draw (whitespace and
+    /// zero-width joiners rasterize to an empty `Image`) and for color
+    /// glyphs (emoji, from a fallback color font): the cache texture is
+    /// `R8Unorm`, one byte per pixel, and `Content::Color`/`SubpixelMask`
+    /// images are 4 bytes per pixel, so uploading them as-is would read
+    /// mis-strided data into the cache.
+    fn rasterize(
+        &mut self,
+        device: &mut wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        cache_key: CacheKey,
+    ) -> Option<CachedGlyph> {
+        let image = self
+            .swash_cache
+            .get_image_uncached(&mut self.font_system, cache_key)
+            .unwrap_or_default();
+
+        if image.data.is_empty() || image.content != SwashContent::Mask {
+            return None;
+        }
+
+        let rect = self
+            .pipeline
+            .allocate_cache_rect(image.placement.width as u16, image.placement.height as u16);
+        self.pipeline
+            .update_cache(device, encoder, rect.offset, rect.size, &image.data);
+
+        Some(CachedGlyph {
+            rect,
+            left: image.placement.left,
+            top: image.placement.top,
+        })
+    }
+
+    /// Draws all glyphs queued since the last call, the same way
+    /// [`crate::GlyphBrush::draw_queued`] draws `glyph_brush` instances.
+    pub fn draw_queued(
+        &mut self,
+        device: &mut wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        depth_stencil_attachment: wgpu::RenderPassDepthStencilAttachmentDescriptor<
+            &wgpu::TextureView,
+        >,
+        target_width: u32,
+        target_height: u32,
+    ) -> Result<(), String> {
+        self.pipeline.upload(device, encoder, &self.queued);
+        let instance_count = self.queued.len() as u32;
+        self.queued.clear();
+
+        self.pipeline.update_transform(
+            device,
+            encoder,
+            crate::orthographic_projection(target_width, target_height),
+        );
+
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                attachment: target,
+                resolve_target: None,
+                load_op: wgpu::LoadOp::Load,
+                store_op: wgpu::StoreOp::Store,
+                clear_color: wgpu::Color::TRANSPARENT,
+            }],
+            depth_stencil_attachment: Some(depth_stencil_attachment),
+        });
+
+        self.pipeline
+            .draw_range(&mut pass, DepthMode::Write, 0..instance_count);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,535 @@
+//! A fast text renderer for [`wgpu`], powered by [`glyph_brush`].
+//!
+//! ```no_run
+//! # use wgpu_glyph::{GlyphBrushBuilder, Section};
+//! # fn build(device: &mut wgpu::Device, render_format: wgpu::TextureFormat) {
+//! let font: &[u8] = include_bytes!("../examples/Inconsolata-Regular.ttf");
+//! let mut glyph_brush = GlyphBrushBuilder::using_font_bytes(font)
+//!     .build(device, render_format);
+//! # }
+//! ```
+//!
+//! [`wgpu`]: https://github.com/gfx-rs/wgpu-rs
+//! [`glyph_brush`]: https://github.com/alexheretic/glyph-brush
+
+mod pipeline;
+
+#[cfg(feature = "cosmic-text")]
+pub mod cosmic;
+
+pub use glyph_brush::{
+    rusttype::{self, PositionedGlyph, Scale},
+    BuiltInLineBreaker, FontId, GlyphCruncher, GlyphPositioner, HorizontalAlign, Layout,
+    LineBreak, LineBreaker, OwnedSectionText, OwnedVariedSection, Section, SectionGeometry,
+    SectionText, VariedSection, VerticalAlign,
+};
+
+use pipeline::{DepthMode, Instance, Pipeline};
+use std::ops::Range;
+
+/// The region of a target `TextureView` [`GlyphBrush::draw_queued_to`]
+/// renders into, and the transform applied to glyph pixel coordinates in
+/// the vertex shader to get them there.
+///
+/// This lets queued sections be drawn into an offscreen render target, an
+/// atlas tile, or a sub-rectangle of a frame, rather than only the
+/// full-surface target [`GlyphBrush::draw_queued`] assumes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    /// Origin, in pixels, of the viewport within the target `TextureView`.
+    pub origin: [u32; 2],
+    /// Size, in pixels, of the viewport within the target `TextureView`.
+    pub size: [u32; 2],
+    /// Column-major transform applied to glyph pixel coordinates in the
+    /// vertex shader, mapping them into clip space.
+    pub transform: [f32; 16],
+}
+
+impl Viewport {
+    /// A viewport covering the full `width`x`height` target with the
+    /// top-left-origin orthographic projection [`GlyphBrush::draw_queued`]
+    /// uses internally.
+    pub fn full_screen(width: u32, height: u32) -> Viewport {
+        Viewport {
+            origin: [0, 0],
+            size: [width, height],
+            transform: orthographic_projection(width, height),
+        }
+    }
+}
+
+/// A section queued via [`GlyphBrush::queue`] or [`GlyphBrush::queue_clipped`],
+/// held until the next `draw_queued`-family call rather than handed
+/// straight to the inner `glyph_brush`.
+struct QueuedSection {
+    section: glyph_brush::OwnedVariedSection,
+    clip_bounds: Option<[f32; 4]>,
+}
+
+/// A clip rectangle, and the range within the final uploaded instance
+/// buffer its glyphs occupy, registered by [`GlyphBrush::queue_clipped`].
+struct ClipRegion {
+    bounds: [f32; 4],
+    glyphs: Range<usize>,
+}
+
+/// A handle to a font and glyph cache, able to queue and draw text against
+/// a render target sharing an associated depth/stencil format.
+///
+/// Borrows its fonts' bytes for `'font`, matching the [`GlyphBrushBuilder`]
+/// they were loaded through.
+pub struct GlyphBrush<'font, H = glyph_brush::DefaultSectionHasher> {
+    glyph_brush: glyph_brush::GlyphBrush<'font, Instance, H>,
+    pipeline: Pipeline,
+    queued: Vec<QueuedSection>,
+    /// The full flat instance buffer `process_queued` produced last time it
+    /// was called, reused verbatim when it reports `ReDraw` (i.e. every
+    /// queued section is identical to last frame, so nothing needs
+    /// re-rasterizing) since `ReDraw` carries no vertex data of its own.
+    last_instances: Vec<Instance>,
+}
+
+impl<'font, H: std::hash::BuildHasher> GlyphBrush<'font, H> {
+    /// Queues a section for drawing on the next call to [`draw_queued`].
+    ///
+    /// [`draw_queued`]: #method.draw_queued
+    pub fn queue<'a, S>(&mut self, section: S)
+    where
+        S: Into<glyph_brush::VariedSection<'a>>,
+    {
+        self.queued.push(QueuedSection {
+            section: section.into().to_owned(),
+            clip_bounds: None,
+        });
+    }
+
+    /// Queues a section the same way [`queue`] does, but restricts its
+    /// glyphs to `clip_bounds` (`[min_x, min_y, max_x, max_y]` in the same
+    /// pixel space as the section's `screen_position`) at pixel
+    /// granularity: glyph quads that extend past the rect are discarded
+    /// there via a stencil test, rather than only being culled whole when
+    /// entirely outside the section's own (coarser) layout bounds.
+    ///
+    /// Builds scrollable panels and similar widgets, where text must be
+    /// clipped to a rectangle even when the quads of the glyphs it
+    /// contains extend past it.
+    ///
+    /// Stencil clipping needs a stencil buffer to write the clip mask into,
+    /// so `clip_bounds` has no effect when drawn with [`draw_queued_to`],
+    /// which renders without any depth/stencil attachment.
+    ///
+    /// This also means the brush must have been built with a
+    /// stencil-bearing [`depth_stencil_state`] (e.g. `Depth24PlusStencil8`,
+    /// as [`GlyphBrushBuilder::depth_stencil_state`]'s default
+    /// `Depth32Float` is not) -- drawing a `queue_clipped` section with
+    /// [`draw_queued`] or [`draw_queued_with_depth_read_only`] against a
+    /// brush that wasn't panics in debug builds.
+    ///
+    /// [`queue`]: #method.queue
+    /// [`draw_queued`]: #method.draw_queued
+    /// [`draw_queued_with_depth_read_only`]: #method.draw_queued_with_depth_read_only
+    /// [`draw_queued_to`]: #method.draw_queued_to
+    /// [`depth_stencil_state`]: GlyphBrushBuilder::depth_stencil_state
+    pub fn queue_clipped<'a, S>(&mut self, section: S, clip_bounds: [f32; 4])
+    where
+        S: Into<glyph_brush::VariedSection<'a>>,
+    {
+        self.queued.push(QueuedSection {
+            section: section.into().to_owned(),
+            clip_bounds: Some(clip_bounds),
+        });
+    }
+
+    /// The depth/stencil format the pipeline's render pipeline was built
+    /// with, as declared through
+    /// [`GlyphBrushBuilder::depth_stencil_state`]. Useful for allocating a
+    /// depth buffer that's guaranteed to be compatible, rather than
+    /// assuming `Depth32Float`.
+    pub fn depth_format(&self) -> wgpu::TextureFormat {
+        self.pipeline.depth_format()
+    }
+
+    /// Allocates a `TextureView` of `width`x`height` suitable for use as the
+    /// depth attachment passed to [`draw_queued`], using this brush's
+    /// [`depth_format`].
+    ///
+    /// [`draw_queued`]: #method.draw_queued
+    /// [`depth_format`]: #method.depth_format
+    pub fn create_depth_texture_view(
+        &self,
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+    ) -> wgpu::TextureView {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth: 1,
+            },
+            array_layer_count: 1,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.depth_format(),
+            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+        });
+
+        texture.create_default_view()
+    }
+
+    /// Draws all queued sections into `target`, sized `target_width`x
+    /// `target_height`, using `depth_stencil_attachment` as the depth
+    /// buffer. Glyphs write and test depth, so later sections are
+    /// correctly occluded by earlier ones drawn closer to the camera.
+    ///
+    /// Call [`queue`] before this to queue sections for drawing.
+    ///
+    /// [`queue`]: #method.queue
+    pub fn draw_queued(
+        &mut self,
+        device: &mut wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        depth_stencil_attachment: wgpu::RenderPassDepthStencilAttachmentDescriptor<
+            &wgpu::TextureView,
+        >,
+        target_width: u32,
+        target_height: u32,
+    ) -> Result<(), String> {
+        self.draw_queued_with_depth(
+            device,
+            encoder,
+            target,
+            Some(depth_stencil_attachment),
+            Viewport::full_screen(target_width, target_height),
+            DepthMode::Write,
+        )
+    }
+
+    /// Like [`draw_queued`], but tests the queued glyphs against
+    /// `depth_stencil_attachment` without writing to it. Pass a `Load`
+    /// depth load op and an undefined/discarded store op in
+    /// `depth_stencil_attachment`: this renders text correctly occluded by
+    /// geometry from an earlier pass (e.g. labels hidden behind walls)
+    /// without clobbering the depth values that pass's later consumers
+    /// still need.
+    ///
+    /// [`draw_queued`]: #method.draw_queued
+    pub fn draw_queued_with_depth_read_only(
+        &mut self,
+        device: &mut wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        depth_stencil_attachment: wgpu::RenderPassDepthStencilAttachmentDescriptor<
+            &wgpu::TextureView,
+        >,
+        target_width: u32,
+        target_height: u32,
+    ) -> Result<(), String> {
+        self.draw_queued_with_depth(
+            device,
+            encoder,
+            target,
+            Some(depth_stencil_attachment),
+            Viewport::full_screen(target_width, target_height),
+            DepthMode::ReadOnly,
+        )
+    }
+
+    /// Draws all queued sections into `target` within `viewport`, rather
+    /// than the full-surface target [`draw_queued`] assumes. This allows
+    /// rendering text into offscreen targets, atlas tiles, or a
+    /// sub-rectangle of a frame, with `viewport.transform` controlling how
+    /// glyph pixel coordinates map into that target.
+    ///
+    /// There is no depth testing against a `target` drawn to this way;
+    /// use [`draw_queued`] or [`draw_queued_with_depth_read_only`] when
+    /// sharing a depth buffer with a 3D scene matters. Since there's no
+    /// depth/stencil attachment at all in this mode, clip bounds set via
+    /// [`queue_clipped`] are not applied -- stencil clipping needs a
+    /// stencil buffer to write the clip mask into.
+    ///
+    /// [`draw_queued`]: #method.draw_queued
+    /// [`draw_queued_with_depth_read_only`]: #method.draw_queued_with_depth_read_only
+    /// [`queue_clipped`]: #method.queue_clipped
+    pub fn draw_queued_to(
+        &mut self,
+        device: &mut wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        viewport: Viewport,
+    ) -> Result<(), String> {
+        self.draw_queued_with_depth(device, encoder, target, None, viewport, DepthMode::None)
+    }
+
+    fn draw_queued_with_depth(
+        &mut self,
+        device: &mut wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        depth_stencil_attachment: Option<
+            wgpu::RenderPassDepthStencilAttachmentDescriptor<&wgpu::TextureView>,
+        >,
+        viewport: Viewport,
+        depth_mode: DepthMode,
+    ) -> Result<(), String> {
+        let pipeline = &mut self.pipeline;
+        let glyph_brush = &mut self.glyph_brush;
+        let queued = std::mem::take(&mut self.queued);
+
+        // `process_queued` is meant to be driven once per frame with every
+        // section already queued into it: its `ReDraw` result is relative to
+        // the *previous call to it*, so calling it once per section here
+        // would compare this frame's section against whatever was queued
+        // right before it in this same loop, not against last frame's same
+        // section. Instead, queue everything first and call it exactly once,
+        // then split its single flat result back into per-section ranges
+        // using instance counts computed up front from the glyphs each
+        // section actually lays down. `process_queued` (by way of
+        // `Glyphed::ensure_vertices`) drops a glyph from the instance buffer
+        // both when it rasterizes to an empty rect (e.g. whitespace, caught
+        // below by `pixel_bounding_box` returning `None`) *and* when its
+        // pixel rect falls entirely outside the section's own layout bounds
+        // -- so the count has to apply that same bounds test, or a section
+        // whose text overflows a finite `bounds` undercounts relative to
+        // what's actually uploaded.
+        let section_counts: Vec<usize> = queued
+            .iter()
+            .map(|queued| {
+                let geometry = SectionGeometry::from(&queued.section.to_borrowed());
+                let bounds = queued.section.layout.bounds_rect(&geometry);
+
+                glyph_brush
+                    .glyphs(&queued.section)
+                    .filter(|glyph| match glyph.pixel_bounding_box() {
+                        None => false,
+                        Some(pixel_coords) => {
+                            !(pixel_coords.min.x as f32 > bounds.max.x
+                                || pixel_coords.min.y as f32 > bounds.max.y
+                                || bounds.min.x > pixel_coords.max.x as f32
+                                || bounds.min.y > pixel_coords.max.y as f32)
+                        }
+                    })
+                    .count()
+            })
+            .collect();
+
+        for queued in &queued {
+            glyph_brush.queue(&queued.section);
+        }
+
+        let instances = loop {
+            let action = glyph_brush.process_queued(
+                |rect, tex_data| {
+                    pipeline.update_cache(
+                        device,
+                        encoder,
+                        [rect.min.x as u16, rect.min.y as u16],
+                        [rect.width() as u16, rect.height() as u16],
+                        tex_data,
+                    );
+                },
+                Instance::from_vertex,
+            );
+
+            match action {
+                Ok(glyph_brush::BrushAction::Draw(instances)) => break instances,
+                Ok(glyph_brush::BrushAction::ReDraw) => break self.last_instances.clone(),
+                Err(glyph_brush::BrushError::TextureTooSmall { suggested, .. }) => {
+                    let (width, height) = suggested;
+                    glyph_brush.resize_texture(width, height);
+                }
+            }
+        };
+
+        self.last_instances = instances.clone();
+
+        // Glyphs queued via `queue_clipped` get their own range in the
+        // uploaded instance buffer, sliced out using the instance counts
+        // computed above; everything else keeps its place, drawn without a
+        // stencil test.
+        let mut clip_regions = Vec::new();
+        let mut offset = 0;
+        for (queued, count) in queued.iter().zip(&section_counts) {
+            let range = offset..offset + count;
+            if let Some(bounds) = queued.clip_bounds.filter(|_| depth_mode != DepthMode::None) {
+                clip_regions.push(ClipRegion {
+                    bounds,
+                    glyphs: range,
+                });
+            }
+            offset += count;
+        }
+
+        debug_assert!(
+            clip_regions.is_empty() || pipeline.depth_format() == wgpu::TextureFormat::Depth24PlusStencil8,
+            "queue_clipped needs a stencil-bearing depth_stencil_state (e.g. Depth24PlusStencil8), \
+             but this GlyphBrush was built with {:?}",
+            pipeline.depth_format(),
+        );
+
+        pipeline.upload(device, encoder, &instances);
+        pipeline.update_transform(device, encoder, viewport.transform);
+
+        let clip_rects: Vec<Instance> = clip_regions
+            .iter()
+            .map(|region| Instance::clip_rect(region.bounds))
+            .collect();
+        pipeline.upload_clip_rects(device, encoder, &clip_rects);
+
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                attachment: target,
+                resolve_target: None,
+                load_op: wgpu::LoadOp::Load,
+                store_op: wgpu::StoreOp::Store,
+                clear_color: wgpu::Color::TRANSPARENT,
+            }],
+            depth_stencil_attachment,
+        });
+
+        pass.set_viewport(
+            viewport.origin[0] as f32,
+            viewport.origin[1] as f32,
+            viewport.size[0] as f32,
+            viewport.size[1] as f32,
+            0.0,
+            1.0,
+        );
+
+        let mut cursor = 0;
+        for (index, region) in clip_regions.iter().enumerate() {
+            pipeline.draw_range(
+                &mut pass,
+                depth_mode,
+                cursor as u32..region.glyphs.start as u32,
+            );
+            pipeline.draw_clip_region(
+                &mut pass,
+                depth_mode == DepthMode::Write,
+                index as u32,
+                region.glyphs.start as u32..region.glyphs.end as u32,
+            );
+            cursor = region.glyphs.end;
+        }
+        pipeline.draw_range(&mut pass, depth_mode, cursor as u32..instances.len() as u32);
+
+        Ok(())
+    }
+}
+
+/// Builds a [`GlyphBrush`], configuring the font set and the depth/stencil
+/// state the glyph render pipeline is created with.
+pub struct GlyphBrushBuilder<'a, H = glyph_brush::DefaultSectionHasher> {
+    inner: glyph_brush::GlyphBrushBuilder<'a, H>,
+    depth_stencil_state: wgpu::DepthStencilStateDescriptor,
+}
+
+impl<'a> GlyphBrushBuilder<'a> {
+    /// Creates a builder with a single font loaded from its raw bytes.
+    pub fn using_font_bytes(font_data: &'a [u8]) -> Self {
+        GlyphBrushBuilder {
+            inner: glyph_brush::GlyphBrushBuilder::using_font_bytes(font_data),
+            depth_stencil_state: default_depth_stencil_state(),
+        }
+    }
+
+    /// Creates a builder with multiple fonts loaded from their raw bytes.
+    pub fn using_fonts_bytes<B: Into<rusttype::SharedBytes<'a>>>(font_data: Vec<B>) -> Self {
+        GlyphBrushBuilder {
+            inner: glyph_brush::GlyphBrushBuilder::using_fonts_bytes(font_data),
+            depth_stencil_state: default_depth_stencil_state(),
+        }
+    }
+}
+
+impl<'a, H: std::hash::BuildHasher> GlyphBrushBuilder<'a, H> {
+    /// Sets the depth/stencil state the glyph render pipeline, and the
+    /// depth attachment expected by [`GlyphBrush::draw_queued`], are built
+    /// against.
+    ///
+    /// Defaults to `Depth32Float` with a `Greater` comparison, which is
+    /// what the built-in render pipeline used before this was
+    /// configurable. Passing e.g. `Depth24PlusStencil8` here lets a caller
+    /// share one depth buffer between their own 3D pass and the text pass
+    /// without a format mismatch panic, and a reversed-Z `Less` comparison
+    /// works the same way.
+    ///
+    /// [`GlyphBrush::queue_clipped`] additionally needs a stencil-bearing
+    /// format here (i.e. `Depth24PlusStencil8`, not the default) to write
+    /// its clip mask into -- see its docs.
+    ///
+    /// [`GlyphBrush::queue_clipped`]: GlyphBrush::queue_clipped
+    pub fn depth_stencil_state(
+        self,
+        depth_stencil_state: wgpu::DepthStencilStateDescriptor,
+    ) -> Self {
+        GlyphBrushBuilder {
+            depth_stencil_state,
+            ..self
+        }
+    }
+
+    /// Sets the section hasher used to cache layout results between
+    /// frames. See [`glyph_brush::GlyphBrushBuilder::section_hasher`].
+    pub fn section_hasher<T: std::hash::BuildHasher>(
+        self,
+        section_hasher: T,
+    ) -> GlyphBrushBuilder<'a, T> {
+        GlyphBrushBuilder {
+            inner: self.inner.section_hasher(section_hasher),
+            depth_stencil_state: self.depth_stencil_state,
+        }
+    }
+
+    /// Builds a [`GlyphBrush`], creating its render pipeline against
+    /// `render_format` and the depth/stencil state configured on this
+    /// builder.
+    pub fn build(
+        self,
+        device: &mut wgpu::Device,
+        render_format: wgpu::TextureFormat,
+    ) -> GlyphBrush<'a, H> {
+        let glyph_brush = self.inner.build();
+        let (cache_width, cache_height) = glyph_brush.texture_dimensions();
+
+        let pipeline = Pipeline::new(
+            device,
+            render_format,
+            self.depth_stencil_state,
+            cache_width,
+            cache_height,
+        );
+
+        GlyphBrush {
+            glyph_brush,
+            pipeline,
+            queued: Vec::new(),
+            last_instances: Vec::new(),
+        }
+    }
+}
+
+fn default_depth_stencil_state() -> wgpu::DepthStencilStateDescriptor {
+    wgpu::DepthStencilStateDescriptor {
+        format: wgpu::TextureFormat::Depth32Float,
+        depth_write_enabled: true,
+        depth_compare: wgpu::CompareFunction::Greater,
+        stencil_front: wgpu::StencilStateFaceDescriptor::IGNORE,
+        stencil_back: wgpu::StencilStateFaceDescriptor::IGNORE,
+        stencil_read_mask: 0,
+        stencil_write_mask: 0,
+    }
+}
+
+/// A standard top-left-origin orthographic projection mapping
+/// `(0, 0) .. (width, height)` pixel coordinates into clip space.
+pub(crate) fn orthographic_projection(width: u32, height: u32) -> [f32; 16] {
+    [
+        2.0 / width as f32, 0.0, 0.0, 0.0,
+        0.0, 2.0 / height as f32, 0.0, 0.0,
+        0.0, 0.0, 1.0, 0.0,
+        -1.0, -1.0, 0.0, 1.0,
+    ]
+}
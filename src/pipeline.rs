@@ -0,0 +1,769 @@
+use bytemuck::{Pod, Zeroable};
+use std::io::Cursor;
+use std::mem;
+use std::ops::Range;
+
+/// A single glyph quad, as uploaded to the GPU cache instance buffer.
+///
+/// The layout mirrors the vertex attributes declared in `shader/text.vert`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct Instance {
+    left_top: [f32; 3],
+    right_bottom: [f32; 2],
+    tex_left_top: [f32; 2],
+    tex_right_bottom: [f32; 2],
+    color: [f32; 4],
+}
+
+impl Instance {
+    /// Builds an instance quad covering `bounds` (`[min_x, min_y, max_x,
+    /// max_y]`), used to write a clip region into the stencil buffer. Its
+    /// texture coordinates and color are irrelevant: the clip pipeline's
+    /// fragment shader ignores both and its color writes are masked off.
+    pub fn clip_rect(bounds: [f32; 4]) -> Instance {
+        Instance {
+            left_top: [bounds[0], bounds[3], 0.0],
+            right_bottom: [bounds[2], bounds[1]],
+            tex_left_top: [0.0, 0.0],
+            tex_right_bottom: [0.0, 0.0],
+            color: [0.0, 0.0, 0.0, 0.0],
+        }
+    }
+
+    /// Builds an instance quad for a glyph already rasterized into a
+    /// [`CacheRect`] of `cache_dimensions`, placed at `(x, y)` in pixels.
+    pub fn from_cache_rect(
+        rect: CacheRect,
+        cache_dimensions: (u16, u16),
+        x: f32,
+        y: f32,
+        z: f32,
+        color: [f32; 4],
+    ) -> Instance {
+        let (cache_width, cache_height) = cache_dimensions;
+
+        let u_min = rect.offset[0] as f32 / cache_width as f32;
+        let v_min = rect.offset[1] as f32 / cache_height as f32;
+        let u_max = (rect.offset[0] + rect.size[0]) as f32 / cache_width as f32;
+        let v_max = (rect.offset[1] + rect.size[1]) as f32 / cache_height as f32;
+
+        Instance {
+            left_top: [x, y, z],
+            right_bottom: [x + rect.size[0] as f32, y + rect.size[1] as f32],
+            tex_left_top: [u_min, v_min],
+            tex_right_bottom: [u_max, v_max],
+            color,
+        }
+    }
+
+    pub fn from_vertex(vertex: glyph_brush::GlyphVertex) -> Instance {
+        let glyph_brush::GlyphVertex {
+            mut tex_coords,
+            pixel_coords,
+            bounds,
+            color,
+            z,
+        } = vertex;
+
+        let gl_bounds = bounds;
+
+        let mut gl_rect = ab_glyph_rect(pixel_coords);
+
+        // Clip the quad against the section bounds, adjusting texture
+        // coordinates to match so clipped glyphs still sample correctly.
+        if gl_rect.max.x > gl_bounds.max.x {
+            let old_width = gl_rect.width();
+            gl_rect.max.x = gl_bounds.max.x;
+            tex_coords.max.x = tex_coords.min.x
+                + (tex_coords.max.x - tex_coords.min.x) * gl_rect.width() / old_width;
+        }
+        if gl_rect.min.x < gl_bounds.min.x {
+            let old_width = gl_rect.width();
+            gl_rect.min.x = gl_bounds.min.x;
+            tex_coords.min.x = tex_coords.max.x
+                - (tex_coords.max.x - tex_coords.min.x) * gl_rect.width() / old_width;
+        }
+        if gl_rect.max.y > gl_bounds.max.y {
+            let old_height = gl_rect.height();
+            gl_rect.max.y = gl_bounds.max.y;
+            tex_coords.max.y = tex_coords.min.y
+                + (tex_coords.max.y - tex_coords.min.y) * gl_rect.height() / old_height;
+        }
+        if gl_rect.min.y < gl_bounds.min.y {
+            let old_height = gl_rect.height();
+            gl_rect.min.y = gl_bounds.min.y;
+            tex_coords.min.y = tex_coords.max.y
+                - (tex_coords.max.y - tex_coords.min.y) * gl_rect.height() / old_height;
+        }
+
+        Instance {
+            left_top: [gl_rect.min.x, gl_rect.max.y, z],
+            right_bottom: [gl_rect.max.x, gl_rect.min.y],
+            tex_left_top: [tex_coords.min.x, tex_coords.max.y],
+            tex_right_bottom: [tex_coords.max.x, tex_coords.min.y],
+            color,
+        }
+    }
+}
+
+// Small shim kept local to this module: older `ab_glyph`/`rusttype` rects
+// don't expose `width`/`height`, and pulling in the full crate just for
+// this would be overkill.
+struct Rect {
+    min: glyph_brush::rusttype::Point<f32>,
+    max: glyph_brush::rusttype::Point<f32>,
+}
+
+impl Rect {
+    fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+    fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+}
+
+// `pixel_coords` comes back as a `Rect<i32>`: glyph placement is always
+// whole-pixel, but it's clipped against `bounds`' `Rect<f32>` below, so it's
+// converted to the same type up front.
+fn ab_glyph_rect(pixel_coords: glyph_brush::rusttype::Rect<i32>) -> Rect {
+    Rect {
+        min: glyph_brush::rusttype::point(pixel_coords.min.x as f32, pixel_coords.min.y as f32),
+        max: glyph_brush::rusttype::point(pixel_coords.max.x as f32, pixel_coords.max.y as f32),
+    }
+}
+
+/// Reads the `u32` words `create_shader_module` expects out of the SPIR-V
+/// bytes our `build.rs` writes, which are embedded as a flat byte array via
+/// `include_bytes!`.
+fn read_spirv(bytes: &[u8]) -> Vec<u32> {
+    wgpu::read_spirv(Cursor::new(bytes)).expect("read compiled SPIR-V")
+}
+
+/// A rectangle allocated out of a [`Pipeline`]'s cache texture by
+/// [`Pipeline::allocate_cache_rect`].
+#[derive(Debug, Clone, Copy)]
+pub struct CacheRect {
+    pub offset: [u16; 2],
+    pub size: [u16; 2],
+}
+
+/// Selects which of [`Pipeline`]'s render pipeline variants
+/// [`Pipeline::draw_range`] binds, matching how the active render pass's
+/// depth/stencil attachment (if any) should be treated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepthMode {
+    /// Glyphs write and test depth, as [`crate::GlyphBrush::draw_queued`] does.
+    Write,
+    /// Glyphs test depth without writing it, as
+    /// [`crate::GlyphBrush::draw_queued_with_depth_read_only`] does.
+    ReadOnly,
+    /// The render pass has no depth/stencil attachment at all, as
+    /// [`crate::GlyphBrush::draw_queued_to`] does. A pipeline built with a
+    /// `depth_stencil_state` requires the pass to carry a matching
+    /// attachment, so this mode binds a pipeline built without one instead.
+    /// Stencil clipping isn't available in this mode, since there's no
+    /// stencil buffer to test against.
+    None,
+}
+
+/// The GPU-side half of a [`crate::GlyphBrush`]: render pipeline, cache
+/// texture and the vertex buffer glyph instances are uploaded into.
+pub struct Pipeline {
+    transform: wgpu::Buffer,
+    // Never read again after `new` wires them into `bind_group`, but kept
+    // alive here for as long as the `Pipeline` is: dropping them would free
+    // the GPU-side resources `bind_group` still refers to.
+    #[allow(dead_code)]
+    sampler: wgpu::Sampler,
+    cache: wgpu::Texture,
+    #[allow(dead_code)]
+    cache_view: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+    #[allow(dead_code)]
+    bind_group_layout: wgpu::BindGroupLayout,
+    render_pipeline: wgpu::RenderPipeline,
+    render_pipeline_read_only_depth: wgpu::RenderPipeline,
+    render_pipeline_no_depth: wgpu::RenderPipeline,
+    render_pipeline_clipped: wgpu::RenderPipeline,
+    render_pipeline_clipped_read_only_depth: wgpu::RenderPipeline,
+    clip_pipeline: wgpu::RenderPipeline,
+    instances: wgpu::Buffer,
+    clip_instances: wgpu::Buffer,
+    depth_stencil_state: wgpu::DepthStencilStateDescriptor,
+    cache_width: u16,
+    cache_height: u16,
+    shelf_x: u16,
+    shelf_y: u16,
+    shelf_height: u16,
+}
+
+impl Pipeline {
+    pub fn new(
+        device: &mut wgpu::Device,
+        render_format: wgpu::TextureFormat,
+        depth_stencil_state: wgpu::DepthStencilStateDescriptor,
+        cache_width: u32,
+        cache_height: u32,
+    ) -> Pipeline {
+        let transform = device
+            .create_buffer_mapped(
+                IDENTITY_MATRIX.len(),
+                wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+            )
+            .fill_from_slice(&IDENTITY_MATRIX);
+
+        let cache = device.create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width: cache_width,
+                height: cache_height,
+                depth: 1,
+            },
+            array_layer_count: 1,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsage::COPY_DST | wgpu::TextureUsage::SAMPLED,
+        });
+        let cache_view = cache.create_default_view();
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            lod_min_clamp: -100.0,
+            lod_max_clamp: 100.0,
+            compare_function: wgpu::CompareFunction::Always,
+        });
+
+        let bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                bindings: &[
+                    wgpu::BindGroupLayoutBinding {
+                        binding: 0,
+                        visibility: wgpu::ShaderStage::VERTEX,
+                        ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+                    },
+                    wgpu::BindGroupLayoutBinding {
+                        binding: 1,
+                        visibility: wgpu::ShaderStage::FRAGMENT,
+                        ty: wgpu::BindingType::SampledTexture {
+                            multisampled: false,
+                            dimension: wgpu::TextureViewDimension::D2,
+                        },
+                    },
+                    wgpu::BindGroupLayoutBinding {
+                        binding: 2,
+                        visibility: wgpu::ShaderStage::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler,
+                    },
+                ],
+            });
+
+        let bind_group = Self::create_bind_group(
+            device,
+            &bind_group_layout,
+            &transform,
+            &cache_view,
+            &sampler,
+        );
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            bind_group_layouts: &[&bind_group_layout],
+        });
+
+        let vs_module = device.create_shader_module(&read_spirv(include_bytes!(concat!(
+            env!("OUT_DIR"),
+            "/text.vert.spv"
+        ))));
+        let fs_module = device.create_shader_module(&read_spirv(include_bytes!(concat!(
+            env!("OUT_DIR"),
+            "/text.frag.spv"
+        ))));
+        let clip_fs_module = device.create_shader_module(&read_spirv(include_bytes!(concat!(
+            env!("OUT_DIR"),
+            "/clip.frag.spv"
+        ))));
+
+        let render_pipeline = Self::create_render_pipeline(
+            device,
+            &pipeline_layout,
+            render_format,
+            wgpu::ColorWrite::ALL,
+            Some(&depth_stencil_state),
+            &vs_module,
+            &fs_module,
+        );
+
+        // Shares everything with `render_pipeline` except `depth_write_enabled`,
+        // for `draw_range(_, DepthMode::ReadOnly, _)`: text is still tested
+        // against the depth buffer (so it's occluded by prior geometry) but
+        // never writes to it, leaving it intact for later passes.
+        let read_only_depth_stencil_state = wgpu::DepthStencilStateDescriptor {
+            depth_write_enabled: false,
+            ..depth_stencil_state.clone()
+        };
+        let render_pipeline_read_only_depth = Self::create_render_pipeline(
+            device,
+            &pipeline_layout,
+            render_format,
+            wgpu::ColorWrite::ALL,
+            Some(&read_only_depth_stencil_state),
+            &vs_module,
+            &fs_module,
+        );
+
+        // For `draw_queued_to`: the render pass it draws into may have no
+        // depth/stencil attachment at all, and a pipeline declaring a
+        // `depth_stencil_state` requires the pass to carry one to match.
+        // This variant declares none, so it binds to such a pass; it can't
+        // be used with `draw_clip_region` since there's no stencil buffer
+        // to clip against.
+        let render_pipeline_no_depth = Self::create_render_pipeline(
+            device,
+            &pipeline_layout,
+            render_format,
+            wgpu::ColorWrite::ALL,
+            None,
+            &vs_module,
+            &fs_module,
+        );
+
+        // Used by `draw_clip_region` for the glyphs of a `queue_clipped`
+        // section: only fragments where the stencil buffer equals the
+        // reference value written by `clip_pipeline` for that region's
+        // clip rect survive, discarding glyph pixels outside it.
+        let clipped_stencil_face = wgpu::StencilStateFaceDescriptor {
+            compare: wgpu::CompareFunction::Equal,
+            fail_op: wgpu::StencilOperation::Keep,
+            depth_fail_op: wgpu::StencilOperation::Keep,
+            pass_op: wgpu::StencilOperation::Keep,
+        };
+        let render_pipeline_clipped = Self::create_render_pipeline(
+            device,
+            &pipeline_layout,
+            render_format,
+            wgpu::ColorWrite::ALL,
+            Some(&wgpu::DepthStencilStateDescriptor {
+                stencil_front: clipped_stencil_face.clone(),
+                stencil_back: clipped_stencil_face.clone(),
+                stencil_read_mask: 0xff,
+                stencil_write_mask: 0,
+                ..depth_stencil_state.clone()
+            }),
+            &vs_module,
+            &fs_module,
+        );
+        let render_pipeline_clipped_read_only_depth = Self::create_render_pipeline(
+            device,
+            &pipeline_layout,
+            render_format,
+            wgpu::ColorWrite::ALL,
+            Some(&wgpu::DepthStencilStateDescriptor {
+                stencil_front: clipped_stencil_face.clone(),
+                stencil_back: clipped_stencil_face,
+                stencil_read_mask: 0xff,
+                stencil_write_mask: 0,
+                ..read_only_depth_stencil_state
+            }),
+            &vs_module,
+            &fs_module,
+        );
+
+        // Writes the stencil reference value over a clip rect ahead of
+        // `render_pipeline_clipped`, independent of depth: color writes are
+        // masked off and depth is ignored, so this only ever marks which
+        // pixels later glyphs are allowed to land on.
+        let clip_stencil_face = wgpu::StencilStateFaceDescriptor {
+            compare: wgpu::CompareFunction::Always,
+            fail_op: wgpu::StencilOperation::Keep,
+            depth_fail_op: wgpu::StencilOperation::Keep,
+            pass_op: wgpu::StencilOperation::Replace,
+        };
+        let clip_pipeline = Self::create_render_pipeline(
+            device,
+            &pipeline_layout,
+            render_format,
+            wgpu::ColorWrite::empty(),
+            Some(&wgpu::DepthStencilStateDescriptor {
+                format: depth_stencil_state.format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil_front: clip_stencil_face.clone(),
+                stencil_back: clip_stencil_face,
+                stencil_read_mask: 0,
+                stencil_write_mask: 0xff,
+            }),
+            &vs_module,
+            &clip_fs_module,
+        );
+
+        let instances = device.create_buffer(&wgpu::BufferDescriptor {
+            size: mem::size_of::<Instance>() as u64,
+            usage: wgpu::BufferUsage::VERTEX | wgpu::BufferUsage::COPY_DST,
+        });
+        let clip_instances = device.create_buffer(&wgpu::BufferDescriptor {
+            size: mem::size_of::<Instance>() as u64,
+            usage: wgpu::BufferUsage::VERTEX | wgpu::BufferUsage::COPY_DST,
+        });
+
+        Pipeline {
+            transform,
+            sampler,
+            cache,
+            cache_view,
+            bind_group,
+            bind_group_layout,
+            render_pipeline,
+            render_pipeline_read_only_depth,
+            render_pipeline_no_depth,
+            render_pipeline_clipped,
+            render_pipeline_clipped_read_only_depth,
+            clip_pipeline,
+            instances,
+            clip_instances,
+            depth_stencil_state,
+            cache_width: cache_width as u16,
+            cache_height: cache_height as u16,
+            shelf_x: 0,
+            shelf_y: 0,
+            shelf_height: 0,
+        }
+    }
+
+    /// Allocates a `width`x`height` rectangle out of the cache texture
+    /// using a simple shelf packer: rectangles are placed left-to-right on
+    /// the current shelf, and a new shelf is started below once one stops
+    /// fitting. Callers needing eviction or growth (as `glyph_brush`'s own
+    /// packer does for [`crate::GlyphBrush`]) should handle it themselves;
+    /// this is meant for brushes, like [`crate::cosmic::CosmicGlyphBrush`],
+    /// that manage their own cache of previously rasterized glyphs and so
+    /// never need to evict.
+    pub fn allocate_cache_rect(&mut self, width: u16, height: u16) -> CacheRect {
+        if self.shelf_x + width > self.cache_width {
+            self.shelf_x = 0;
+            self.shelf_y += self.shelf_height;
+            self.shelf_height = 0;
+        }
+
+        assert!(
+            self.shelf_y + height <= self.cache_height,
+            "glyph cache texture exhausted; grow it in Pipeline::new"
+        );
+
+        let offset = [self.shelf_x, self.shelf_y];
+
+        self.shelf_x += width;
+        self.shelf_height = self.shelf_height.max(height);
+
+        CacheRect {
+            offset,
+            size: [width, height],
+        }
+    }
+
+    /// The dimensions of the cache texture, for normalizing a
+    /// [`CacheRect`] into texture coordinates.
+    pub fn cache_dimensions(&self) -> (u16, u16) {
+        (self.cache_width, self.cache_height)
+    }
+
+    /// The depth/stencil format this pipeline's render pipeline was built
+    /// against, so callers can allocate a compatible depth buffer of their
+    /// own rather than guessing at `Depth32Float`.
+    pub fn depth_format(&self) -> wgpu::TextureFormat {
+        self.depth_stencil_state.format
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        transform: &wgpu::Buffer,
+        cache_view: &wgpu::TextureView,
+        sampler: &wgpu::Sampler,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            bindings: &[
+                wgpu::Binding {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer {
+                        buffer: transform,
+                        range: 0..64,
+                    },
+                },
+                wgpu::Binding {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(cache_view),
+                },
+                wgpu::Binding {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        })
+    }
+
+    fn create_render_pipeline(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        render_format: wgpu::TextureFormat,
+        color_write_mask: wgpu::ColorWrite,
+        depth_stencil_state: Option<&wgpu::DepthStencilStateDescriptor>,
+        vs_module: &wgpu::ShaderModule,
+        fs_module: &wgpu::ShaderModule,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            layout,
+            vertex_stage: wgpu::ProgrammableStageDescriptor {
+                module: vs_module,
+                entry_point: "main",
+            },
+            fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+                module: fs_module,
+                entry_point: "main",
+            }),
+            rasterization_state: Some(wgpu::RasterizationStateDescriptor {
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: wgpu::CullMode::None,
+                depth_bias: 0,
+                depth_bias_slope_scale: 0.0,
+                depth_bias_clamp: 0.0,
+            }),
+            primitive_topology: wgpu::PrimitiveTopology::TriangleList,
+            color_states: &[wgpu::ColorStateDescriptor {
+                format: render_format,
+                color_blend: wgpu::BlendDescriptor {
+                    src_factor: wgpu::BlendFactor::SrcAlpha,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha_blend: wgpu::BlendDescriptor {
+                    src_factor: wgpu::BlendFactor::SrcAlpha,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                write_mask: color_write_mask,
+            }],
+            depth_stencil_state: depth_stencil_state.cloned(),
+            index_format: wgpu::IndexFormat::Uint16,
+            vertex_buffers: &[wgpu::VertexBufferDescriptor {
+                stride: mem::size_of::<Instance>() as wgpu::BufferAddress,
+                step_mode: wgpu::InputStepMode::Instance,
+                attributes: &[
+                    wgpu::VertexAttributeDescriptor {
+                        offset: 0,
+                        format: wgpu::VertexFormat::Float3,
+                        shader_location: 0,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: 4 * 3,
+                        format: wgpu::VertexFormat::Float2,
+                        shader_location: 1,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: 4 * 5,
+                        format: wgpu::VertexFormat::Float2,
+                        shader_location: 2,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: 4 * 7,
+                        format: wgpu::VertexFormat::Float2,
+                        shader_location: 3,
+                    },
+                    wgpu::VertexAttributeDescriptor {
+                        offset: 4 * 9,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 4,
+                    },
+                ],
+            }],
+            sample_count: 1,
+            sample_mask: !0,
+            alpha_to_coverage_enabled: false,
+        })
+    }
+
+    /// Uploads the transform matrix used to map glyph pixel coordinates
+    /// into clip space.
+    pub fn update_transform(&mut self, device: &mut wgpu::Device, encoder: &mut wgpu::CommandEncoder, transform: [f32; 16]) {
+        let temp_buffer = device
+            .create_buffer_mapped(transform.len(), wgpu::BufferUsage::COPY_SRC)
+            .fill_from_slice(&transform);
+        encoder.copy_buffer_to_buffer(&temp_buffer, 0, &self.transform, 0, 64);
+    }
+
+    pub fn upload(
+        &mut self,
+        device: &mut wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        instances: &[Instance],
+    ) {
+        if instances.is_empty() {
+            return;
+        }
+
+        let new_buffer = device
+            .create_buffer_mapped(
+                instances.len(),
+                wgpu::BufferUsage::VERTEX | wgpu::BufferUsage::COPY_DST,
+            )
+            .fill_from_slice(instances);
+
+        let size = mem::size_of_val(instances) as wgpu::BufferAddress;
+        encoder.copy_buffer_to_buffer(&new_buffer, 0, &self.instances, 0, size);
+
+        self.instances = new_buffer;
+    }
+
+    /// Uploads the clip rect quads drawn by [`draw_clip_region`] ahead of
+    /// each [`GlyphBrush::queue_clipped`] section's glyphs. Kept in a buffer
+    /// of their own, separate from the glyph instance buffer, so the glyph
+    /// ranges passed to [`draw_clip_region`] index glyph instances only.
+    ///
+    /// [`draw_clip_region`]: Pipeline::draw_clip_region
+    /// [`GlyphBrush::queue_clipped`]: crate::GlyphBrush::queue_clipped
+    pub fn upload_clip_rects(
+        &mut self,
+        device: &mut wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        clip_rects: &[Instance],
+    ) {
+        if clip_rects.is_empty() {
+            return;
+        }
+
+        let new_buffer = device
+            .create_buffer_mapped(
+                clip_rects.len(),
+                wgpu::BufferUsage::VERTEX | wgpu::BufferUsage::COPY_DST,
+            )
+            .fill_from_slice(clip_rects);
+
+        let size = mem::size_of_val(clip_rects) as wgpu::BufferAddress;
+        encoder.copy_buffer_to_buffer(&new_buffer, 0, &self.clip_instances, 0, size);
+
+        self.clip_instances = new_buffer;
+    }
+
+    pub fn update_cache(
+        &mut self,
+        device: &mut wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        offset: [u16; 2],
+        size: [u16; 2],
+        data: &[u8],
+    ) {
+        let buffer = device
+            .create_buffer_mapped(data.len(), wgpu::BufferUsage::COPY_SRC)
+            .fill_from_slice(data);
+
+        encoder.copy_buffer_to_texture(
+            wgpu::BufferCopyView {
+                buffer: &buffer,
+                offset: 0,
+                row_pitch: size[0] as u32,
+                image_height: size[1] as u32,
+            },
+            wgpu::TextureCopyView {
+                texture: &self.cache,
+                mip_level: 0,
+                array_layer: 0,
+                origin: wgpu::Origin3d {
+                    x: offset[0] as f32,
+                    y: offset[1] as f32,
+                    z: 0.0,
+                },
+            },
+            wgpu::Extent3d {
+                width: size[0] as u32,
+                height: size[1] as u32,
+                depth: 1,
+            },
+        );
+    }
+
+    /// Draws `range` of the currently uploaded instances into `pass`,
+    /// without any stencil test.
+    ///
+    /// `depth_mode` selects which of the render pipelines built in
+    /// [`Pipeline::new`] to bind, matching how `pass`'s depth/stencil
+    /// attachment (if any) should be treated.
+    pub fn draw_range<'p>(
+        &'p self,
+        pass: &mut wgpu::RenderPass<'p>,
+        depth_mode: DepthMode,
+        range: Range<u32>,
+    ) {
+        if range.is_empty() {
+            return;
+        }
+
+        let pipeline = match depth_mode {
+            DepthMode::Write => &self.render_pipeline,
+            DepthMode::ReadOnly => &self.render_pipeline_read_only_depth,
+            DepthMode::None => &self.render_pipeline_no_depth,
+        };
+
+        pass.set_pipeline(pipeline);
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.set_vertex_buffers(0, &[(&self.instances, 0)]);
+        pass.draw(0..6, range);
+    }
+
+    /// Draws a [`crate::GlyphBrush::queue_clipped`] section's glyphs,
+    /// clipped pixel-accurately to its clip rect: first writes
+    /// `clip_rect_index`'s quad (uploaded via [`upload_clip_rects`]) into
+    /// the stencil buffer, then draws `glyph_range` of the glyph instance
+    /// buffer with a stencil-equal test against it, discarding glyph pixels
+    /// that land outside the rect even when their quad extends past it.
+    ///
+    /// The stencil buffer is only cleared once, at the start of the whole
+    /// pass, so each region is written with its own reference value
+    /// (`clip_rect_index + 1`, never `0`, the cleared value) rather than a
+    /// constant `1`: otherwise a later region's glyphs would also pass the
+    /// `Equal` test anywhere an earlier region left its mark, bleeding
+    /// through into overlapping regions.
+    ///
+    /// [`upload_clip_rects`]: Pipeline::upload_clip_rects
+    pub fn draw_clip_region<'p>(
+        &'p self,
+        pass: &mut wgpu::RenderPass<'p>,
+        depth_write_enabled: bool,
+        clip_rect_index: u32,
+        glyph_range: Range<u32>,
+    ) {
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.set_stencil_reference(clip_rect_index + 1);
+
+        pass.set_pipeline(&self.clip_pipeline);
+        pass.set_vertex_buffers(0, &[(&self.clip_instances, 0)]);
+        pass.draw(0..6, clip_rect_index..clip_rect_index + 1);
+
+        if glyph_range.is_empty() {
+            return;
+        }
+
+        let pipeline = if depth_write_enabled {
+            &self.render_pipeline_clipped
+        } else {
+            &self.render_pipeline_clipped_read_only_depth
+        };
+
+        pass.set_pipeline(pipeline);
+        pass.set_vertex_buffers(0, &[(&self.instances, 0)]);
+        pass.draw(0..6, glyph_range);
+    }
+}
+
+pub const IDENTITY_MATRIX: [f32; 16] = [
+    1.0, 0.0, 0.0, 0.0, //
+    0.0, 1.0, 0.0, 0.0, //
+    0.0, 0.0, 1.0, 0.0, //
+    0.0, 0.0, 0.0, 1.0, //
+];